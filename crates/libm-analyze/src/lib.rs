@@ -8,15 +8,31 @@ use quote::quote;
 use std::collections::HashSet;
 use syn::parse_macro_input;
 
-/// `input` contains a single identifier, corresponding to a user-defined macro.
-/// This identifier is expanded for each libm public API.
+/// `input` contains a single identifier, corresponding to a user-defined
+/// macro, optionally followed by a braced filter selecting which APIs to
+/// expand it for:
+///
+/// ```ignore
+/// for_each_api!(my_macro { ignore: "jnf,foo", only: "sin,cos", ret: f64, max_args: 2 });
+/// ```
+///
+/// Recognized filter keys (all optional): `ignore` and `only` each take a
+/// comma-separated string literal of function names; `ret` takes a type and
+/// keeps only functions returning it; `min_args`/`max_args` take integer
+/// literals bounding the number of arguments.
+///
+/// This identifier is expanded for each libm public API, receiving the
+/// function's attributes (e.g. `#[inline]`, `#[no_panic]`, any `#[cfg]`) and
+/// a `float_width` of `F32`, `F64`, or `Mixed`, alongside its id and
+/// signature, so consumers can partition generated code by precision or
+/// cfg-gating without re-parsing the libm sources themselves.
 ///
 /// See tests/analyze or below for the API.
 #[proc_macro]
 pub fn for_each_api(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as Input);
     let files = get_libm_files();
-    let functions = get_functions(&files, &input.ignored);
+    let functions = get_functions(&files, &input.filter);
     let mut tokens = proc_macro2::TokenStream::new();
     let input_macro = input.macro_id;
     for function in functions {
@@ -25,10 +41,14 @@ pub fn for_each_api(input: TokenStream) -> TokenStream {
         let ret_ty = function.ret_ty;
         let arg_tys = function.arg_tys;
         let arg_ids = get_arg_ids(arg_tys.len());
+        let attrs = function.attrs;
+        let float_width = float_width(&arg_tys, &ret_ty);
         let t = quote! {
             #input_macro! {
                 id: #id;
                 api_kind: #api_kind;
+                attrs: #(#attrs)*;
+                float_width: #float_width;
                 arg_tys: #(#arg_tys),*;
                 arg_ids: #(#arg_ids),*;
                 ret_ty: #ret_ty;
@@ -87,6 +107,7 @@ struct FnSig {
     c_abi: bool,
     ret_ty: Option<syn::Type>,
     arg_tys: Vec<syn::Type>,
+    attrs: Vec<syn::Attribute>,
 }
 
 impl FnSig {
@@ -105,13 +126,40 @@ macro_rules! syn_to_str {
     }};
 }
 
+/// Recursively collects every `syn::Item` reachable from `items`, descending
+/// into inline modules (`mod foo { ... }`) so that functions nested inside
+/// submodules are visible to `get_functions` just like top-level ones.
+///
+/// Modules without an inline body (`mod foo;`, declared in a separate file)
+/// are not followed here: `get_libm_files` already walks the whole source
+/// tree and parses every `.rs` file independently, so their contents show up
+/// as their own top-level items.
+fn collect_items<'a>(items: &'a [syn::Item], out: &mut Vec<&'a syn::Item>) {
+    for item in items {
+        if let syn::Item::Mod(syn::ItemMod {
+            content: Some((_, items)),
+            ..
+        }) = item
+        {
+            collect_items(items, out);
+        }
+        out.push(item);
+    }
+}
+
 /// Extracts all public functions from the libm files while
 /// doing some sanity checks on the function signatures.
-fn get_functions(files: &[syn::File], ignored: &Option<HashSet<String>>) -> Vec<FnSig> {
+fn get_functions(files: &[syn::File], filter: &Filter) -> Vec<FnSig> {
     let mut error = false;
     let mut functions = Vec::new();
+    // Traverse all files, recursively descending into inline submodules, to
+    // collect every item:
+    let mut items = Vec::new();
+    for file in files {
+        collect_items(&file.items, &mut items);
+    }
     // Traverse all files matching function items
-    for item in files.iter().flat_map(|f| f.items.iter()) {
+    for item in items {
         let mut e = false;
         if let syn::Item::Fn(syn::ItemFn {
             vis: syn::Visibility::Public(_),
@@ -132,10 +180,15 @@ fn get_functions(files: &[syn::File], ignored: &Option<HashSet<String>>) -> Vec<
                 c_abi: false,
                 arg_tys: Vec::new(),
                 ret_ty: None,
+                attrs: attrs.clone(),
             };
-            // Skip ignored functions:
-            if let Some(ignored) = ignored {
-                if ignored.contains(&fn_sig.name()) {
+            // Skip functions excluded by name, or not explicitly included
+            // when an include set is given:
+            if filter.ignore.contains(&fn_sig.name()) {
+                continue;
+            }
+            if let Some(only) = &filter.only {
+                if !only.contains(&fn_sig.name()) {
                     continue;
                 }
             }
@@ -252,6 +305,26 @@ fn get_functions(files: &[syn::File], ignored: &Option<HashSet<String>>) -> Vec<
                     )),
                 }
             }
+            // Apply the return-type and arity constraints, if any:
+            if let Some(ret) = &filter.ret {
+                let matches = fn_sig
+                    .ret_ty
+                    .as_ref()
+                    .is_some_and(|t| syn_to_str!(t.clone()) == syn_to_str!(ret.clone()));
+                if !matches {
+                    continue;
+                }
+            }
+            if let Some(min_args) = filter.min_args {
+                if fn_sig.arg_tys.len() < min_args {
+                    continue;
+                }
+            }
+            if let Some(max_args) = filter.max_args {
+                if fn_sig.arg_tys.len() > max_args {
+                    continue;
+                }
+            }
             // If there was an error, we skip the function.
             // Otherwise, the user macro is expanded with
             // the function:
@@ -314,6 +387,37 @@ fn get_arg_ids(len: usize) -> Vec<syn::Ident> {
     ids
 }
 
+/// Returns the identifier name of a (possibly pointed-to) `syn::Type::Path`,
+/// e.g. `Some("f32")` for both `f32` and `*const f32`.
+fn ty_name(t: &syn::Type) -> Option<String> {
+    match t {
+        syn::Type::Ptr(p) => ty_name(&p.elem),
+        syn::Type::Path(p) => Some(p.path.segments.first()?.into_value().ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Classifies a function signature by the float widths appearing in its
+/// arguments and return type: `F32` if only `f32` is used, `F64` if only
+/// `f64` is used, `Mixed` otherwise (e.g. `jn`, which also takes an `i32`
+/// order, or functions using both widths).
+fn float_width(arg_tys: &[syn::Type], ret_ty: &Option<syn::Type>) -> syn::Ident {
+    let (mut has_f32, mut has_f64, mut has_other) = (false, false, false);
+    for ty in arg_tys.iter().chain(ret_ty.iter()) {
+        match ty_name(ty).as_deref() {
+            Some("f32") => has_f32 = true,
+            Some("f64") => has_f64 = true,
+            _ => has_other = true,
+        }
+    }
+    let name = match (has_f32, has_f64, has_other) {
+        (true, false, false) => "F32",
+        (false, true, false) => "F64",
+        _ => "Mixed",
+    };
+    syn::Ident::new(name, proc_macro2::Span::call_site())
+}
+
 /// Returns the `ApiKind` enum variant for this function
 fn to_api_kind(id: syn::Ident) -> syn::Ident {
     let name = syn_to_str!(id);
@@ -323,38 +427,84 @@ fn to_api_kind(id: syn::Ident) -> syn::Ident {
     syn::Ident::new(&name, proc_macro2::Span::call_site())
 }
 
-#[derive(Debug)]
 struct Input {
     macro_id: syn::Ident,
-    ignored: Option<HashSet<String>>,
+    filter: Filter,
+}
+
+/// A structured selection of which APIs `for_each_api!` should expand the
+/// user macro for. See `for_each_api`'s docs for the surface syntax.
+#[derive(Default)]
+struct Filter {
+    ignore: HashSet<String>,
+    only: Option<HashSet<String>>,
+    ret: Option<syn::Type>,
+    min_args: Option<usize>,
+    max_args: Option<usize>,
+}
+
+/// Parses a comma-separated string literal into a set of names, e.g.
+/// `"jnf,foo"` -> `{"jnf", "foo"}`.
+fn parse_name_set(input: syn::parse::ParseStream) -> syn::Result<HashSet<String>> {
+    let lit: syn::LitStr = input.parse()?;
+    Ok(lit.value().split(',').map(str::to_string).collect())
 }
 
 impl syn::parse::Parse for Input {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let content;
         let macro_id: syn::Ident = input.parse()?;
-        let lookahead = input.lookahead1();
-        if lookahead.peek(syn::token::Paren) {
-            let _paren_token = syn::parenthesized!(content in input);
-            let ignored: syn::Lit = content.parse::<syn::Lit>()?;
-            if let syn::Lit::Str(c) = ignored {
-                let s = c.value();
-                let mut hash_set = HashSet::<String>::new();
-                for i in s.split(',') {
-                    hash_set.insert(i.to_string());
-                }
-                Ok(Self {
-                    macro_id,
-                    ignored: Some(hash_set),
-                })
-            } else {
-                Err(lookahead.error())
+        let mut filter = Filter::default();
+        if input.peek(syn::token::Brace) {
+            let content;
+            syn::braced!(content in input);
+            let fields = content.parse_terminated::<_, syn::Token![,]>(FilterField::parse)?;
+            for field in fields {
+                field.apply(&mut filter);
             }
-        } else {
-            Ok(Self {
-                macro_id,
-                ignored: None,
-            })
+        }
+        Ok(Self { macro_id, filter })
+    }
+}
+
+/// A single `key: value` entry of the filter grammar.
+enum FilterField {
+    Ignore(HashSet<String>),
+    Only(HashSet<String>),
+    Ret(syn::Type),
+    MinArgs(usize),
+    MaxArgs(usize),
+}
+
+impl FilterField {
+    fn apply(self, filter: &mut Filter) {
+        match self {
+            FilterField::Ignore(names) => filter.ignore = names,
+            FilterField::Only(names) => filter.only = Some(names),
+            FilterField::Ret(ty) => filter.ret = Some(ty),
+            FilterField::MinArgs(n) => filter.min_args = Some(n),
+            FilterField::MaxArgs(n) => filter.max_args = Some(n),
+        }
+    }
+}
+
+impl syn::parse::Parse for FilterField {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let key: syn::Ident = input.parse()?;
+        input.parse::<syn::Token![:]>()?;
+        match key.to_string().as_str() {
+            "ignore" => Ok(FilterField::Ignore(parse_name_set(input)?)),
+            "only" => Ok(FilterField::Only(parse_name_set(input)?)),
+            "ret" => Ok(FilterField::Ret(input.parse()?)),
+            "min_args" => Ok(FilterField::MinArgs(input.parse::<syn::LitInt>()?.value() as usize)),
+            "max_args" => Ok(FilterField::MaxArgs(input.parse::<syn::LitInt>()?.value() as usize)),
+            other => Err(syn::Error::new(
+                key.span(),
+                format!(
+                    "unknown `for_each_api!` filter key `{}` \
+                     (expected one of: ignore, only, ret, min_args, max_args)",
+                    other
+                ),
+            )),
         }
     }
 }
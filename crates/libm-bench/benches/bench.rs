@@ -1,6 +1,9 @@
 #![feature(test)]
 extern crate test;
 
+#[path = "domain.rs"]
+mod domain;
+
 use libm_test::{ApiKind, CallFn};
 use rand::Rng;
 use test::Bencher;
@@ -9,6 +12,8 @@ macro_rules! bench_fn {
     (
         id: $id:ident;
         api_kind: $api_kind:ident;
+        attrs: $(#[$attrs:meta])*;
+        float_width: $float_width:ident;
         arg_tys: $($arg_tys:ty),*;
         arg_ids: $($arg_ids:ident),*;
         ret_ty: $ret_ty:ty;
@@ -19,18 +24,18 @@ macro_rules! bench_fn {
             type FnTy
                 = unsafe extern "C" fn ($($arg_ids: $arg_tys),*) -> $ret_ty;
 
-            // Generate a tuple of arguments containing random values:
+            // Generate a tuple of arguments, clamped into each argument's
+            // valid/interesting domain via the `domain` registry:
             let mut rng = rand::thread_rng();
-            let mut x: ( $($arg_tys,)+ ) = ( $(rng.gen::<$arg_tys>(),)+ );
-
-            if let ApiKind::Jn = ApiKind::$api_kind {
-                let ptr = &mut x as *mut _ as *mut i32;
-                unsafe { ptr.write(ptr.read() & 0xffff) };
-            }
+            let x: ( $($arg_tys,)+ ) = ( $(domain::clamp(
+                ApiKind::$api_kind,
+                domain::arg_index(stringify!($arg_ids)),
+                rng.gen::<$arg_tys>(),
+            ),)+ );
 
             bh.iter(|| test::black_box(x).call(libm::$id as FnTy))
         }
     };
 }
 
-libm_analyze::for_each_api!(bench_fn(/*ignore:*/ "jnf"));
+libm_analyze::for_each_api!(bench_fn { ignore: "jnf" });
@@ -0,0 +1,84 @@
+//! Per-function input-domain descriptors.
+//!
+//! Blindly sampling `rng.gen()` produces meaningless or invalid inputs for
+//! many functions: `acos`/`asin` are only defined on `[-1, 1]`, `log` and
+//! `sqrt` are uninteresting (or `NaN`) on negative inputs, `jn`'s first
+//! argument is a small integer order rather than an arbitrary `i32`, etc.
+//! This module is the single place that knows how to clamp/transform a raw
+//! random (or special-value) sample into a given function's valid or
+//! "interesting" range, so both the bench and accuracy-test macros produce
+//! representative inputs instead of mostly-`NaN` garbage.
+
+use libm_test::ApiKind;
+
+/// Clamps/transforms `x`, the raw sample for argument `arg` (0-indexed) of
+/// `api_kind`, into that argument's valid or "interesting" range. Functions
+/// with no entry below are left untouched, i.e. they default to the full
+/// range of their type.
+pub fn clamp<T: Domain>(api_kind: ApiKind, arg: usize, x: T) -> T {
+    Domain::clamp(api_kind, arg, x)
+}
+
+/// Implemented for every scalar type a libm function can take, so `clamp`
+/// can be generic over the argument's type.
+pub trait Domain: Sized {
+    fn clamp(api_kind: ApiKind, arg: usize, x: Self) -> Self;
+}
+
+impl Domain for f32 {
+    fn clamp(api_kind: ApiKind, arg: usize, x: Self) -> Self {
+        match (api_kind, arg) {
+            (ApiKind::Acosf, 0) | (ApiKind::Asinf, 0) => x.clamp(-1.0, 1.0),
+            (ApiKind::Logf, 0) | (ApiKind::Log2f, 0) | (ApiKind::Log10f, 0) | (ApiKind::Sqrtf, 0) => {
+                x.abs()
+            }
+            _ => x,
+        }
+    }
+}
+
+impl Domain for f64 {
+    fn clamp(api_kind: ApiKind, arg: usize, x: Self) -> Self {
+        match (api_kind, arg) {
+            (ApiKind::Acos, 0) | (ApiKind::Asin, 0) => x.clamp(-1.0, 1.0),
+            (ApiKind::Log, 0) | (ApiKind::Log2, 0) | (ApiKind::Log10, 0) | (ApiKind::Sqrt, 0) => {
+                x.abs()
+            }
+            _ => x,
+        }
+    }
+}
+
+impl Domain for i32 {
+    fn clamp(api_kind: ApiKind, arg: usize, x: Self) -> Self {
+        match (api_kind, arg) {
+            // `jn`/`jnf`'s first argument is a small integer order; masking
+            // it keeps the remaining bits as noise but bounds the magnitude.
+            (ApiKind::Jn, 0) | (ApiKind::Jnf, 0) => x & 0xffff,
+            _ => x,
+        }
+    }
+}
+
+macro_rules! impl_domain_default {
+    ($($ty:ty),*) => {
+        $(
+            impl Domain for $ty {
+                fn clamp(_api_kind: ApiKind, _arg: usize, x: Self) -> Self {
+                    x
+                }
+            }
+        )*
+    };
+}
+
+impl_domain_default!(i8, i16, i64, isize, u8, u16, u32, u64, usize);
+
+/// Recovers the 0-indexed argument position from an `x{n}` identifier, as
+/// generated by `libm_analyze::for_each_api!`'s `arg_ids`.
+pub fn arg_index(arg_id: &str) -> usize {
+    arg_id
+        .trim_start_matches('x')
+        .parse()
+        .unwrap_or_else(|_| panic!("not an `x{{n}}` argument id: {}", arg_id))
+}
@@ -0,0 +1,198 @@
+//! Differential accuracy tests.
+//!
+//! For every function exported by `for_each_api!`, compares `libm`'s
+//! implementation against the platform's C `libm` of the same name and ABI
+//! on a battery of special values plus `SAMPLES` random inputs, failing
+//! if the results are farther apart than the function's ULP threshold.
+
+#[path = "../benches/domain.rs"]
+mod domain;
+
+use libm_test::{ApiKind, CallFn};
+use rand::Rng;
+
+/// Random samples drawn per function, in addition to the special values.
+const SAMPLES: usize = 100;
+
+/// ULP threshold used for functions without a function-specific bound in
+/// `ulp_threshold`.
+const DEFAULT_ULP_THRESHOLD: u64 = 4;
+
+/// A fixed battery of "interesting" values for a scalar type. For floats:
+/// signed zeros, infinities, NaN, the smallest subnormal, one, and the type's
+/// maximum finite value. For integers: zero, a few small values, and the
+/// type's extreme values.
+trait Specials: Sized + Copy {
+    const SPECIALS: [Self; 8];
+}
+
+impl Specials for f32 {
+    const SPECIALS: [f32; 8] = [
+        0.0,
+        -0.0,
+        f32::INFINITY,
+        f32::NEG_INFINITY,
+        f32::NAN,
+        f32::from_bits(1), // smallest subnormal
+        1.0,
+        f32::MAX,
+    ];
+}
+
+impl Specials for f64 {
+    const SPECIALS: [f64; 8] = [
+        0.0,
+        -0.0,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NAN,
+        f64::from_bits(1), // smallest subnormal
+        1.0,
+        f64::MAX,
+    ];
+}
+
+macro_rules! impl_specials_for_int {
+    ($($ty:ty),*) => {
+        $(
+            impl Specials for $ty {
+                const SPECIALS: [$ty; 8] = [0, 1, <$ty>::MAX, <$ty>::MAX - 1, 2, 3, 4, 5];
+            }
+        )*
+    };
+}
+
+impl_specials_for_int!(u8, u16, u32, u64, usize);
+
+macro_rules! impl_specials_for_signed_int {
+    ($($ty:ty),*) => {
+        $(
+            impl Specials for $ty {
+                const SPECIALS: [$ty; 8] = [0, 1, -1, <$ty>::MIN, <$ty>::MAX, 2, -2, 10];
+            }
+        )*
+    };
+}
+
+impl_specials_for_signed_int!(i8, i16, i32, i64, isize);
+
+macro_rules! impl_ulp_diff {
+    ($name:ident, $float:ty, $int:ty, $wide:ty) => {
+        /// ULP distance between two values of this width. `NaN` vs `NaN` is
+        /// treated as a pass; infinities require bit-exact equality.
+        fn $name(a: $float, b: $float) -> u64 {
+            if a.is_nan() && b.is_nan() {
+                return 0;
+            }
+            if a.is_infinite() || b.is_infinite() {
+                return if a.to_bits() == b.to_bits() {
+                    0
+                } else {
+                    u64::max_value()
+                };
+            }
+            let order = |bits: $int| -> $int {
+                if bits < 0 {
+                    <$int>::min_value().wrapping_sub(bits)
+                } else {
+                    bits
+                }
+            };
+            let ai = order(a.to_bits() as $int);
+            let bi = order(b.to_bits() as $int);
+            // Widen to a type one size up before subtracting: `ai`/`bi` span
+            // the full range of `$int`, so their difference can exceed what
+            // `$int` itself can represent and wrap back around to a small
+            // (wrong) distance if computed at that width.
+            (ai as $wide - bi as $wide).unsigned_abs() as u64
+        }
+    };
+}
+
+impl_ulp_diff!(ulp_diff_f32, f32, i32, i64);
+impl_ulp_diff!(ulp_diff_f64, f64, i64, i128);
+
+/// Asserts that `got` is within `max_ulp` ULPs of `want`.
+trait AssertUlpClose {
+    fn assert_ulp_close(self, want: Self, max_ulp: u64);
+}
+
+macro_rules! impl_assert_ulp_close {
+    ($float:ty, $ulp_diff:ident) => {
+        impl AssertUlpClose for $float {
+            fn assert_ulp_close(self, want: Self, max_ulp: u64) {
+                let diff = $ulp_diff(self, want);
+                assert!(
+                    diff <= max_ulp,
+                    "got {} ULPs away from the reference (max {}): {} vs {}",
+                    diff,
+                    max_ulp,
+                    self,
+                    want
+                );
+            }
+        }
+    };
+}
+
+impl_assert_ulp_close!(f32, ulp_diff_f32);
+impl_assert_ulp_close!(f64, ulp_diff_f64);
+
+/// Per-function ULP threshold. Functions not listed use
+/// `DEFAULT_ULP_THRESHOLD`.
+fn ulp_threshold(api_kind: ApiKind) -> u64 {
+    match api_kind {
+        ApiKind::Jn | ApiKind::Jnf => 20,
+        _ => DEFAULT_ULP_THRESHOLD,
+    }
+}
+
+macro_rules! accuracy_fn {
+    (
+        id: $id:ident;
+        api_kind: $api_kind:ident;
+        attrs: $(#[$attrs:meta])*;
+        float_width: $float_width:ident;
+        arg_tys: $($arg_tys:ty),*;
+        arg_ids: $($arg_ids:ident),*;
+        ret_ty: $ret_ty:ty;
+    ) => {
+        #[test]
+        #[allow(unused_mut)]
+        pub fn $id() {
+            type FnTy = unsafe extern "C" fn($($arg_ids: $arg_tys),*) -> $ret_ty;
+
+            #[link(name = "m")]
+            extern "C" {
+                fn $id($($arg_ids: $arg_tys),*) -> $ret_ty;
+            }
+
+            let mut rng = rand::thread_rng();
+            let threshold = ulp_threshold(ApiKind::$api_kind);
+
+            let check = |x: ($($arg_tys,)*)| {
+                let got = x.call(libm::$id as FnTy);
+                let want = x.call($id as FnTy);
+                got.assert_ulp_close(want, threshold);
+            };
+
+            // The special-value battery is left unclamped: ±0/±∞ domain-edge
+            // cases (e.g. `sqrt(-inf)`, `acos(-inf)`) are exactly what this
+            // battery exists to check both implementations agree on, and
+            // clamping them into the function's valid domain would hide
+            // those checks instead of exercising them.
+            for i in 0..8 {
+                check(($(<$arg_tys as Specials>::SPECIALS[i],)*));
+            }
+            for _ in 0..SAMPLES {
+                check(($(domain::clamp(
+                    ApiKind::$api_kind,
+                    domain::arg_index(stringify!($arg_ids)),
+                    rng.gen::<$arg_tys>(),
+                ),)*));
+            }
+        }
+    };
+}
+
+libm_analyze::for_each_api!(accuracy_fn { ignore: "jnf" });